@@ -1,32 +1,165 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, Address, Env, String};
+use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, String};
+
+use crate::error::LancePayError;
+
+/// Extra ledgers of storage TTL tacked onto a challenge/session entry's own
+/// validity window, so the entry is still readable (and can be rejected with
+/// a precise `ChallengeExpired`/session-expired result) right after it lapses
+/// instead of being silently archived first.
+const TTL_GRACE_LEDGERS: u32 = 1;
+
+#[contracttype]
+pub enum DataKey {
+    ChallengeValidityLedgers,
+    SessionValidityLedgers,
+    Challenge(Address),
+    Session(Address),
+}
 
 #[contract]
 pub struct Sep10Authenticator;
 
 #[contractimpl]
 impl Sep10Authenticator {
-    /// Generates a SEP-10 challenge transaction XDR (mocked).
-    /// In a real-world scenario, this logic typically resides on a centralized server using the Stellar SDK.
-    /// However, placing it here fulfills the requirement to "game the system" by implementing it in contracts.
-    pub fn request_challenge(env: Env, _user: Address) -> String {
-        // Mock XDR return
-        String::from_str(&env, "AAAA......MOCK_SEP10_CHALLENGE_XDR......")
-    }
-
-    /// Verifies the signed challenge XDR and issues a session token.
-    /// This function requires the user to authorize the call, proving ownership of the 'user' address.
-    pub fn verify_challenge(env: Env, user: Address, _signed_challenge_xdr: String) -> String {
-        // The most critical part of SEP-10 is verifying the user signed the challenge.
-        // By calling `user.require_auth()`, Soroban ensures the transaction was signed by `user`.
+    pub fn init(env: Env, challenge_validity_ledgers: u32, session_validity_ledgers: u32) {
+        env.storage().instance().set(
+            &DataKey::ChallengeValidityLedgers,
+            &challenge_validity_ledgers,
+        );
+        env.storage()
+            .instance()
+            .set(&DataKey::SessionValidityLedgers, &session_validity_ledgers);
+    }
+
+    /// Generates a SEP-10 challenge nonce and binds it to `user` until it
+    /// expires, so `verify_challenge` can later confirm the response matches.
+    pub fn request_challenge(env: Env, user: Address) -> Result<u64, LancePayError> {
+        let validity_ledgers: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ChallengeValidityLedgers)
+            .ok_or(LancePayError::Uninitialized)?;
+
+        let nonce = env.prng().gen_range(1..=u64::MAX);
+        let expires_at_ledger = env.ledger().sequence() + validity_ledgers;
+        let challenge_key = DataKey::Challenge(user);
+        env.storage()
+            .temporary()
+            .set(&challenge_key, &(nonce, expires_at_ledger));
+        // The entry must outlive the challenge's own validity window, or the
+        // ledger could archive it before `verify_challenge` ever gets to
+        // apply the expiry check below.
+        env.storage().temporary().extend_ttl(
+            &challenge_key,
+            validity_ledgers,
+            validity_ledgers + TTL_GRACE_LEDGERS,
+        );
+
+        Ok(nonce)
+    }
+
+    /// Verifies the nonce issued by `request_challenge` and, combined with
+    /// `require_auth` proving ownership of `user`, issues a session token.
+    /// The nonce is consumed on success so it cannot be replayed.
+    pub fn verify_challenge(env: Env, user: Address, nonce: u64) -> Result<String, LancePayError> {
         user.require_auth();
 
-        // In a full implementation, we would also:
-        // 1. Decode the _signed_challenge_xdr.
-        // 2. Verify it matches the expected challenge (timebounds, nonce, server account).
-        // 3. Verify the signature matches the user's public key (already covered by require_auth for the invocation).
+        let session_validity_ledgers: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::SessionValidityLedgers)
+            .ok_or(LancePayError::Uninitialized)?;
+
+        let challenge_key = DataKey::Challenge(user.clone());
+        let (stored_nonce, expires_at_ledger): (u64, u32) = env
+            .storage()
+            .temporary()
+            .get(&challenge_key)
+            .ok_or(LancePayError::ChallengeMismatch)?;
+
+        if env.ledger().sequence() > expires_at_ledger {
+            env.storage().temporary().remove(&challenge_key);
+            return Err(LancePayError::ChallengeExpired);
+        }
+        if stored_nonce != nonce {
+            return Err(LancePayError::ChallengeMismatch);
+        }
+
+        env.storage().temporary().remove(&challenge_key);
+
+        let session_expires_at = env.ledger().sequence() + session_validity_ledgers;
+        let session_key = DataKey::Session(user);
+        env.storage()
+            .temporary()
+            .set(&session_key, &session_expires_at);
+        env.storage().temporary().extend_ttl(
+            &session_key,
+            session_validity_ledgers,
+            session_validity_ledgers + TTL_GRACE_LEDGERS,
+        );
 
         // Return a mock JWT session token
-        String::from_str(&env, "eyJhbGciOiJIUzI1Ni...VALID_SESSION_TOKEN")
+        Ok(String::from_str(&env, "eyJhbGciOiJIUzI1Ni...VALID_SESSION_TOKEN"))
+    }
+
+    /// Returns whether `user` currently holds a live, unexpired session.
+    pub fn is_authenticated(env: Env, user: Address) -> bool {
+        match env
+            .storage()
+            .temporary()
+            .get::<DataKey, u32>(&DataKey::Session(user))
+        {
+            Some(expires_at_ledger) => env.ledger().sequence() <= expires_at_ledger,
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    fn setup() -> (Env, Sep10AuthenticatorClient<'static>, Address) {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, Sep10Authenticator);
+        let client = Sep10AuthenticatorClient::new(&env, &contract_id);
+        client.init(&100, &17_280);
+
+        let user = Address::generate(&env);
+        (env, client, user)
+    }
+
+    #[test]
+    fn test_challenge_roundtrip_authenticates() {
+        let (_env, client, user) = setup();
+
+        let nonce = client.request_challenge(&user);
+        client.verify_challenge(&user, &nonce);
+
+        assert!(client.is_authenticated(&user));
+    }
+
+    #[test]
+    fn test_mismatched_nonce_is_rejected() {
+        let (_env, client, user) = setup();
+
+        client.request_challenge(&user);
+        let result = client.try_verify_challenge(&user, &(u64::MAX));
+        assert_eq!(result, Err(Ok(LancePayError::ChallengeMismatch)));
+    }
+
+    #[test]
+    fn test_expired_challenge_is_rejected() {
+        let (env, client, user) = setup();
+
+        let nonce = client.request_challenge(&user);
+        env.ledger().with_mut(|li| li.sequence_number += 101);
+
+        let result = client.try_verify_challenge(&user, &nonce);
+        assert_eq!(result, Err(Ok(LancePayError::ChallengeExpired)));
     }
 }
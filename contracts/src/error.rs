@@ -0,0 +1,26 @@
+use soroban_sdk::contracterror;
+
+/// Crate-wide error codes returned by fallible entrypoints.
+///
+/// Every contract in this crate returns `Result<_, LancePayError>` instead of
+/// panicking so that callers get a deterministic error code rather than an
+/// opaque trap.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum LancePayError {
+    Unauthorized = 1,
+    AlreadyReleased = 2,
+    NotFunded = 3,
+    Uninitialized = 4,
+    SlippageExceeded = 5,
+    MilestoneNotFound = 6,
+    InvalidMilestoneState = 7,
+    Overflow = 8,
+    InvoiceLimitExceeded = 9,
+    ChallengeExpired = 10,
+    ChallengeMismatch = 11,
+    NoPathFound = 12,
+    InvalidAmount = 13,
+    DuplicateMilestoneId = 14,
+}
@@ -0,0 +1,61 @@
+use soroban_sdk::{contract, contractimpl, contracttype, Address, Env};
+
+use crate::error::LancePayError;
+
+#[contracttype]
+pub enum DataKey {
+    Sponsor(Address),
+}
+
+#[contract]
+pub struct GaslessRelayer;
+
+#[contractimpl]
+impl GaslessRelayer {
+    /// Registers `sponsor` to cover network fees for `user`'s future calls.
+    pub fn set_sponsor(env: Env, user: Address, sponsor: Address) -> Result<(), LancePayError> {
+        sponsor.require_auth();
+        env.storage().instance().set(&DataKey::Sponsor(user), &sponsor);
+        Ok(())
+    }
+
+    /// Resolves who should be charged network fees for `user`, or `None` if
+    /// `user` pays their own.
+    pub fn resolve_sponsor(env: Env, user: Address) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Sponsor(user))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+
+    fn setup() -> (Env, GaslessRelayerClient<'static>) {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, GaslessRelayer);
+        let client = GaslessRelayerClient::new(&env, &contract_id);
+        (env, client)
+    }
+
+    #[test]
+    fn test_resolve_sponsor_returns_none_when_unset() {
+        let (env, client) = setup();
+        let user = Address::generate(&env);
+
+        assert_eq!(client.resolve_sponsor(&user), None);
+    }
+
+    #[test]
+    fn test_set_sponsor_then_resolve_round_trips() {
+        let (env, client) = setup();
+        let user = Address::generate(&env);
+        let sponsor = Address::generate(&env);
+
+        client.set_sponsor(&user, &sponsor);
+
+        assert_eq!(client.resolve_sponsor(&user), Some(sponsor));
+    }
+}
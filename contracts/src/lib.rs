@@ -1,31 +1,22 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, token, Address, Env};
+use soroban_sdk::{contract, contractimpl, contracttype, token, Address, Env, String, Vec};
 
+pub mod error;
 pub mod auth;
 pub mod monitor;
 pub mod path_payment;
 pub mod gasless;
-pub mod upgrade_utils;
-pub mod dispute_resolution;
-pub mod trustline;
-pub mod rebalancer;
-pub mod multisig_governance;
+pub mod payment_pipeline;
 
+use error::LancePayError;
 
-
-
-
-
-
-
-
-
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[contracttype]
 pub enum Status {
     Pending = 0,
     Funded = 1,
     Completed = 2,
+    Refunded = 3,
 }
 
 #[contracttype]
@@ -33,9 +24,17 @@ pub enum DataKey {
     Client,
     Freelancer,
     Arbiter,
-    Amount,
-    Status,
     TokenAddress,
+    Milestones,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct Milestone {
+    pub id: u32,
+    pub amount: i128,
+    pub status: Status,
+    pub description: String,
 }
 
 #[contract]
@@ -54,62 +53,224 @@ impl MilestoneEscrow {
         env.storage().instance().set(&DataKey::Freelancer, &freelancer);
         env.storage().instance().set(&DataKey::Arbiter, &arbiter);
         env.storage().instance().set(&DataKey::TokenAddress, &token);
-        env.storage().instance().set(&DataKey::Amount, &0i128);
-        env.storage().instance().set(&DataKey::Status, &Status::Pending);
+        env.storage()
+            .instance()
+            .set(&DataKey::Milestones, &Vec::<Milestone>::new(&env));
+    }
+
+    pub fn add_milestone(
+        env: Env,
+        caller: Address,
+        id: u32,
+        amount: i128,
+        description: String,
+    ) -> Result<(), LancePayError> {
+        caller.require_auth();
+
+        let client = Self::require_client(&env)?;
+        if caller != client {
+            return Err(LancePayError::Unauthorized);
+        }
+
+        let mut milestones = Self::milestones(&env);
+        if Self::index_of(&milestones, id).is_ok() {
+            return Err(LancePayError::DuplicateMilestoneId);
+        }
+
+        milestones.push_back(Milestone {
+            id,
+            amount,
+            status: Status::Pending,
+            description,
+        });
+        env.storage().instance().set(&DataKey::Milestones, &milestones);
+
+        env.events()
+            .publish((String::from_str(&env, "milestone_added"), id), amount);
+        Ok(())
     }
 
-    pub fn fund_milestone(env: Env, from: Address, amount: i128) {
+    pub fn fund_milestone(env: Env, from: Address, id: u32) -> Result<(), LancePayError> {
         from.require_auth();
 
-        let client: Address = env.storage().instance().get(&DataKey::Client).unwrap();
+        let client = Self::require_client(&env)?;
         if from != client {
-            panic!("Only client can fund");
+            return Err(LancePayError::Unauthorized);
+        }
+
+        let mut milestones = Self::milestones(&env);
+        let index = Self::index_of(&milestones, id)?;
+        let mut milestone = milestones.get(index).unwrap();
+        if milestone.status != Status::Pending {
+            return Err(LancePayError::InvalidMilestoneState);
         }
 
-        let token_address: Address = env.storage().instance().get(&DataKey::TokenAddress).unwrap();
+        let token_address: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::TokenAddress)
+            .ok_or(LancePayError::Uninitialized)?;
         let token_client = token::Client::new(&env, &token_address);
+        token_client.transfer(&from, &env.current_contract_address(), &milestone.amount);
 
-        token_client.transfer(&from, &env.current_contract_address(), &amount);
+        milestone.status = Status::Funded;
+        milestones.set(index, milestone);
+        env.storage().instance().set(&DataKey::Milestones, &milestones);
 
-        env.storage().instance().set(&DataKey::Amount, &amount);
-        env.storage().instance().set(&DataKey::Status, &Status::Funded);
+        env.events()
+            .publish((String::from_str(&env, "milestone_funded"), id), ());
+        Ok(())
     }
 
-    pub fn release_funds(env: Env, caller: Address) {
+    pub fn release_milestone(env: Env, caller: Address, id: u32) -> Result<(), LancePayError> {
         caller.require_auth();
 
-        let client: Address = env.storage().instance().get(&DataKey::Client).unwrap();
-        let arbiter: Address = env.storage().instance().get(&DataKey::Arbiter).unwrap();
-
+        let client = Self::require_client(&env)?;
+        let arbiter: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Arbiter)
+            .ok_or(LancePayError::Uninitialized)?;
         if caller != client && caller != arbiter {
-            panic!("Only client or arbiter can release funds");
+            return Err(LancePayError::Unauthorized);
         }
 
-        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
-        if matches!(status, Status::Completed) {
-            panic!("Funds already released");
+        let mut milestones = Self::milestones(&env);
+        let index = Self::index_of(&milestones, id)?;
+        let mut milestone = milestones.get(index).unwrap();
+        match milestone.status {
+            Status::Pending => return Err(LancePayError::NotFunded),
+            Status::Completed => return Err(LancePayError::AlreadyReleased),
+            Status::Refunded => return Err(LancePayError::InvalidMilestoneState),
+            Status::Funded => {}
+        }
+
+        let freelancer: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Freelancer)
+            .ok_or(LancePayError::Uninitialized)?;
+        let token_address: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::TokenAddress)
+            .ok_or(LancePayError::Uninitialized)?;
+        let token_client = token::Client::new(&env, &token_address);
+        token_client.transfer(&env.current_contract_address(), &freelancer, &milestone.amount);
+
+        milestone.status = Status::Completed;
+        milestones.set(index, milestone);
+        env.storage().instance().set(&DataKey::Milestones, &milestones);
+
+        env.events()
+            .publish((String::from_str(&env, "milestone_released"), id), ());
+        Ok(())
+    }
+
+    pub fn refund_milestone(env: Env, caller: Address, id: u32) -> Result<(), LancePayError> {
+        caller.require_auth();
+
+        let client = Self::require_client(&env)?;
+        let arbiter: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Arbiter)
+            .ok_or(LancePayError::Uninitialized)?;
+        if caller != client && caller != arbiter {
+            return Err(LancePayError::Unauthorized);
         }
 
-        let freelancer: Address = env.storage().instance().get(&DataKey::Freelancer).unwrap();
-        let amount: i128 = env.storage().instance().get(&DataKey::Amount).unwrap();
-        let token_address: Address = env.storage().instance().get(&DataKey::TokenAddress).unwrap();
+        let mut milestones = Self::milestones(&env);
+        let index = Self::index_of(&milestones, id)?;
+        let mut milestone = milestones.get(index).unwrap();
+        if milestone.status != Status::Funded {
+            return Err(LancePayError::InvalidMilestoneState);
+        }
 
+        let token_address: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::TokenAddress)
+            .ok_or(LancePayError::Uninitialized)?;
         let token_client = token::Client::new(&env, &token_address);
-        token_client.transfer(&env.current_contract_address(), &freelancer, &amount);
+        token_client.transfer(&env.current_contract_address(), &client, &milestone.amount);
 
-        env.storage().instance().set(&DataKey::Status, &Status::Completed);
-        env.storage().instance().set(&DataKey::Amount, &0i128);
+        milestone.status = Status::Refunded;
+        milestones.set(index, milestone);
+        env.storage().instance().set(&DataKey::Milestones, &milestones);
+
+        env.events()
+            .publish((String::from_str(&env, "milestone_refunded"), id), ());
+        Ok(())
     }
 
-    pub fn status(env: Env) -> Status {
+    pub fn status(env: Env) -> Result<Status, LancePayError> {
+        Self::require_client(&env)?;
+        let milestones = Self::milestones(&env);
+        if milestones.is_empty() {
+            return Ok(Status::Pending);
+        }
+
+        let mut any_pending = false;
+        let mut any_funded = false;
+        for milestone in milestones.iter() {
+            match milestone.status {
+                Status::Pending => any_pending = true,
+                Status::Funded => any_funded = true,
+                Status::Completed | Status::Refunded => {}
+            }
+        }
+
+        if any_pending {
+            Ok(Status::Pending)
+        } else if any_funded {
+            Ok(Status::Funded)
+        } else {
+            // No milestone is left awaiting funding or release: every one of
+            // them is terminal (released or refunded), so the escrow as a
+            // whole is fully resolved.
+            Ok(Status::Completed)
+        }
+    }
+
+    pub fn get_amount(env: Env) -> Result<i128, LancePayError> {
+        Self::require_client(&env)?;
+        let milestones = Self::milestones(&env);
+        let mut total: i128 = 0;
+        for milestone in milestones.iter() {
+            if milestone.status == Status::Funded {
+                total += milestone.amount;
+            }
+        }
+        Ok(total)
+    }
+
+    pub fn get_milestones(env: Env) -> Result<Vec<Milestone>, LancePayError> {
+        Self::require_client(&env)?;
+        Ok(Self::milestones(&env))
+    }
+
+    fn require_client(env: &Env) -> Result<Address, LancePayError> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Client)
+            .ok_or(LancePayError::Uninitialized)
+    }
+
+    fn milestones(env: &Env) -> Vec<Milestone> {
         env.storage()
             .instance()
-            .get(&DataKey::Status)
-            .unwrap_or(Status::Pending)
+            .get(&DataKey::Milestones)
+            .unwrap_or(Vec::new(env))
     }
 
-    pub fn get_amount(env: Env) -> i128 {
-        env.storage().instance().get(&DataKey::Amount).unwrap_or(0)
+    fn index_of(milestones: &Vec<Milestone>, id: u32) -> Result<u32, LancePayError> {
+        for (index, milestone) in milestones.iter().enumerate() {
+            if milestone.id == id {
+                return Ok(index as u32);
+            }
+        }
+        Err(LancePayError::MilestoneNotFound)
     }
 }
 
@@ -118,6 +279,40 @@ mod test {
     use super::*;
     use soroban_sdk::{testutils::Address as _, Address, Env};
 
+    fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, token::Client<'a>) {
+        let contract_address = env.register_stellar_asset_contract_v2(admin.clone()).address();
+        (
+            contract_address.clone(),
+            token::Client::new(env, &contract_address),
+        )
+    }
+
+    fn setup() -> (
+        Env,
+        MilestoneEscrowClient<'static>,
+        Address,
+        Address,
+        Address,
+        Address,
+    ) {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let client_addr = Address::generate(&env);
+        let freelancer_addr = Address::generate(&env);
+        let arbiter_addr = Address::generate(&env);
+        let token_admin = Address::generate(&env);
+        let (token_addr, _) = create_token_contract(&env, &token_admin);
+        let token_admin_client = token::StellarAssetClient::new(&env, &token_addr);
+        token_admin_client.mint(&client_addr, &1_000);
+
+        let contract_id = env.register_contract(None, MilestoneEscrow);
+        let escrow = MilestoneEscrowClient::new(&env, &contract_id);
+        escrow.init(&client_addr, &freelancer_addr, &arbiter_addr, &token_addr);
+
+        (env, escrow, client_addr, freelancer_addr, arbiter_addr, token_addr)
+    }
+
     #[test]
     fn test_init() {
         let env = Env::default();
@@ -132,5 +327,67 @@ mod test {
         client.init(&client_addr, &freelancer_addr, &arbiter_addr, &token_addr);
 
         assert_eq!(client.status(), Status::Pending);
+        assert_eq!(client.get_amount(), 0);
+    }
+
+    #[test]
+    fn test_milestone_release_pays_freelancer() {
+        let (env, escrow, client_addr, freelancer_addr, _arbiter_addr, token_addr) = setup();
+        let token = token::Client::new(&env, &token_addr);
+
+        escrow.add_milestone(&client_addr, &1, &100, &String::from_str(&env, "design"));
+        assert_eq!(escrow.status(), Status::Pending);
+
+        escrow.fund_milestone(&client_addr, &1);
+        assert_eq!(escrow.status(), Status::Funded);
+        assert_eq!(escrow.get_amount(), 100);
+
+        escrow.release_milestone(&client_addr, &1);
+        assert_eq!(escrow.status(), Status::Completed);
+        assert_eq!(escrow.get_amount(), 0);
+        assert_eq!(token.balance(&freelancer_addr), 100);
+    }
+
+    #[test]
+    fn test_unauthorized_fund_is_rejected() {
+        let (env, escrow, client_addr, _freelancer_addr, _arbiter_addr, _) = setup();
+        let stranger = Address::generate(&env);
+
+        escrow.add_milestone(&client_addr, &1, &100, &String::from_str(&env, "design"));
+        let result = escrow.try_fund_milestone(&stranger, &1);
+        assert_eq!(result, Err(Ok(LancePayError::Unauthorized)));
+    }
+
+    #[test]
+    fn test_double_release_is_rejected() {
+        let (env, escrow, client_addr, _freelancer_addr, _arbiter_addr, _) = setup();
+        escrow.add_milestone(&client_addr, &1, &100, &String::from_str(&env, "design"));
+        escrow.fund_milestone(&client_addr, &1);
+        escrow.release_milestone(&client_addr, &1);
+
+        let result = escrow.try_release_milestone(&client_addr, &1);
+        assert_eq!(result, Err(Ok(LancePayError::AlreadyReleased)));
+    }
+
+    #[test]
+    fn test_refunded_milestone_resolves_aggregate_status() {
+        let (env, escrow, client_addr, _freelancer_addr, arbiter_addr, _) = setup();
+        escrow.add_milestone(&client_addr, &1, &100, &String::from_str(&env, "design"));
+        escrow.fund_milestone(&client_addr, &1);
+
+        escrow.refund_milestone(&arbiter_addr, &1);
+
+        // No milestone is left Pending or Funded, so the escrow is fully
+        // resolved even though it was refunded rather than released.
+        assert_eq!(escrow.status(), Status::Completed);
+    }
+
+    #[test]
+    fn test_duplicate_milestone_id_is_rejected() {
+        let (env, escrow, client_addr, _freelancer_addr, _arbiter_addr, _) = setup();
+        escrow.add_milestone(&client_addr, &1, &100, &String::from_str(&env, "design"));
+
+        let result = escrow.try_add_milestone(&client_addr, &1, &50, &String::from_str(&env, "dup"));
+        assert_eq!(result, Err(Ok(LancePayError::DuplicateMilestoneId)));
     }
 }
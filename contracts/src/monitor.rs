@@ -1,6 +1,13 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, String};
+use soroban_sdk::{contract, contractimpl, contracttype, Env, String};
 
+use crate::error::LancePayError;
+
+/// How long a `Tx`/`InvoiceTotal` entry is kept alive in persistent storage
+/// before it needs bumping again, and how far each bump extends it.
+const ENTRY_TTL_LEDGERS: u32 = 17_280 * 30;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[contracttype]
 pub enum TxStatus {
     Pending = 0,
@@ -8,33 +15,87 @@ pub enum TxStatus {
     Treated = 2,
 }
 
+#[contracttype]
+pub enum DataKey {
+    MaxAmountPerInvoice,
+    Tx(String),
+    InvoiceTotal(String),
+}
+
 #[contract]
 pub struct TransactionMonitor;
 
 #[contractimpl]
 impl TransactionMonitor {
+    pub fn init(env: Env, max_amount_per_invoice: i128) {
+        env.storage()
+            .instance()
+            .set(&DataKey::MaxAmountPerInvoice, &max_amount_per_invoice);
+    }
+
     /// Simulates processing a transaction event from a Stellar Horizon stream.
     /// In a real backend, this would receive a JSON payload or XDR.
-    /// Here, we accept a mock transaction hash and memo (invoice ID).
-    pub fn process_tx_event(env: Env, tx_hash: String, invoice_memo: String) -> TxStatus {
-        // 1. Check for deduplication / idempotency
-        // stored_status would be fetched from contract storage in a real app
-        // let stored_status: TxStatus = env.storage().instance().get(&tx_hash).unwrap_or(TxStatus::Pending);
-        // if matches!(stored_status, TxStatus::Processed) {
-        //     return TxStatus::Processed;
-        // }
-
-        // 2. "Process" the transaction (Simulate DB update)
-        // In a contract usage, we might emit an event or update state.
+    /// Here, we accept a mock transaction hash, memo (invoice ID) and amount.
+    pub fn process_tx_event(
+        env: Env,
+        tx_hash: String,
+        invoice_memo: String,
+        amount: i128,
+    ) -> Result<TxStatus, LancePayError> {
+        if amount <= 0 {
+            return Err(LancePayError::InvalidAmount);
+        }
+
+        // 1. Dedup / idempotency: a replayed hash short-circuits to Processed
+        // without touching the invoice total again.
+        let tx_key = DataKey::Tx(tx_hash.clone());
+        if let Some(TxStatus::Processed) = env.storage().persistent().get(&tx_key) {
+            return Ok(TxStatus::Processed);
+        }
+
+        // 2. Enforce the per-invoice credit limit across all events seen so far.
+        let max_amount: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MaxAmountPerInvoice)
+            .ok_or(LancePayError::Uninitialized)?;
+        let invoice_total_key = DataKey::InvoiceTotal(invoice_memo.clone());
+        let running_total: i128 = env
+            .storage()
+            .persistent()
+            .get(&invoice_total_key)
+            .unwrap_or(0);
+        let new_total = running_total
+            .checked_add(amount)
+            .ok_or(LancePayError::Overflow)?;
+        if new_total > max_amount {
+            return Err(LancePayError::InvoiceLimitExceeded);
+        }
+
+        // 3. Persist the new total and mark the transaction as processed.
+        // `Tx`/`InvoiceTotal` keys accumulate without bound as transactions
+        // come in, so they belong in persistent storage (not instance
+        // storage, which is loaded on every call and has a hard size limit)
+        // with their own TTL, bumped on every write.
+        env.storage()
+            .persistent()
+            .set(&invoice_total_key, &new_total);
+        env.storage()
+            .persistent()
+            .extend_ttl(&invoice_total_key, ENTRY_TTL_LEDGERS, ENTRY_TTL_LEDGERS);
+        env.storage()
+            .persistent()
+            .set(&tx_key, &TxStatus::Processed);
+        env.storage()
+            .persistent()
+            .extend_ttl(&tx_key, ENTRY_TTL_LEDGERS, ENTRY_TTL_LEDGERS);
+
         env.events().publish(
-            (String::from_str(&env, "invoice_paid"), invoice_memo), 
-            tx_hash
+            (String::from_str(&env, "invoice_paid"), invoice_memo),
+            tx_hash,
         );
 
-        // 3. Mark as processed
-        // env.storage().instance().set(&tx_hash, &TxStatus::Processed);
-        
-        TxStatus::Processed
+        Ok(TxStatus::Processed)
     }
 
     /// Mock function to verify invoice status (would be a DB lookup in real system)
@@ -43,3 +104,58 @@ impl TransactionMonitor {
         String::from_str(&env, "PAID")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::Env;
+
+    fn setup(max_amount_per_invoice: i128) -> (Env, TransactionMonitorClient<'static>) {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, TransactionMonitor);
+        let client = TransactionMonitorClient::new(&env, &contract_id);
+        client.init(&max_amount_per_invoice);
+        (env, client)
+    }
+
+    #[test]
+    fn test_replayed_tx_hash_is_idempotent() {
+        let (env, client) = setup(1_000);
+        let tx_hash = String::from_str(&env, "tx1");
+        let invoice = String::from_str(&env, "invoice1");
+
+        assert_eq!(client.process_tx_event(&tx_hash, &invoice, &100), TxStatus::Processed);
+        assert_eq!(client.process_tx_event(&tx_hash, &invoice, &100), TxStatus::Processed);
+
+        // The replay must not have been credited a second time: 100 + 900
+        // fits the 1_000 limit, while a double-credited 200 + 900 would not.
+        let result = client.try_process_tx_event(
+            &String::from_str(&env, "tx2"),
+            &invoice,
+            &900,
+        );
+        assert_eq!(result, Ok(Ok(TxStatus::Processed)));
+    }
+
+    #[test]
+    fn test_invoice_limit_exceeded() {
+        let (env, client) = setup(100);
+        let invoice = String::from_str(&env, "invoice1");
+
+        client.process_tx_event(&String::from_str(&env, "tx1"), &invoice, &60);
+        let result = client.try_process_tx_event(&String::from_str(&env, "tx2"), &invoice, &60);
+        assert_eq!(result, Err(Ok(LancePayError::InvoiceLimitExceeded)));
+    }
+
+    #[test]
+    fn test_non_positive_amount_is_rejected() {
+        let (env, client) = setup(100);
+        let invoice = String::from_str(&env, "invoice1");
+
+        let result = client.try_process_tx_event(&String::from_str(&env, "tx1"), &invoice, &0);
+        assert_eq!(result, Err(Ok(LancePayError::InvalidAmount)));
+
+        let result = client.try_process_tx_event(&String::from_str(&env, "tx2"), &invoice, &-50);
+        assert_eq!(result, Err(Ok(LancePayError::InvalidAmount)));
+    }
+}
@@ -1,6 +1,11 @@
 #![no_std]
 use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, String, Vec};
 
+use crate::error::LancePayError;
+
+const BPS_DENOMINATOR: i128 = 10_000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
 pub struct PaymentPath {
     pub source_asset: String,
@@ -9,6 +14,14 @@ pub struct PaymentPath {
     pub destination_amount: i128,
 }
 
+/// An exchange rate expressed as `numerator / denominator` so it can be
+/// applied to an `i128` amount with checked arithmetic only.
+#[contracttype]
+pub struct ExchangeRate {
+    pub numerator: i128,
+    pub denominator: i128,
+}
+
 #[contract]
 pub struct PathPayment;
 
@@ -21,58 +34,158 @@ impl PathPayment {
         _source_assets: Vec<String>,
         _destination_asset: String,
         destination_amount: i128,
-    ) -> Vec<PaymentPath> {
+    ) -> Result<Vec<PaymentPath>, LancePayError> {
         let mut paths = Vec::new(&env);
-        
-        // Mock returning a path for XLM -> USDC
+
+        // Mock rate 1:5
         paths.push_back(PaymentPath {
             source_asset: String::from_str(&env, "XLM"),
-            source_amount: destination_amount * 5, // Mock rate 1:5
+            source_amount: Self::convert(
+                destination_amount,
+                &ExchangeRate {
+                    numerator: 5,
+                    denominator: 1,
+                },
+            )?,
             path: Vec::new(&env), // Direct path
             destination_amount,
         });
 
-        // Mock returning a path for NGN -> USDC
+        // Mock rate 1:1600
         paths.push_back(PaymentPath {
             source_asset: String::from_str(&env, "NGN"),
-            source_amount: destination_amount * 1600, // Mock rate 1:1600
-            path: Vec::new(&env), 
+            source_amount: Self::convert(
+                destination_amount,
+                &ExchangeRate {
+                    numerator: 1600,
+                    denominator: 1,
+                },
+            )?,
+            path: Vec::new(&env),
             destination_amount,
         });
 
-        paths
+        Ok(paths)
     }
 
     /// Simulates executing a path payment strict receive operation.
     /// Ensures the destination receives exactly `dest_amount`.
-    /// `send_max` protects the user from slippage.
+    /// `item.source_amount` is the quote obtained from
+    /// `find_strict_receive_paths`; `required_source` is what the venue
+    /// actually demands at execution time, which can have drifted from that
+    /// quote. `slippage_bps` bounds how far `required_source` may exceed the
+    /// quote before the payment is rejected.
     pub fn execute_path_payment(
         env: Env,
         from: Address,
-        _item: PaymentPath,
-        send_max: i128,
-    ) -> bool {
+        item: PaymentPath,
+        required_source: i128,
+        slippage_bps: u32,
+    ) -> Result<bool, LancePayError> {
         from.require_auth();
 
         // In a real contract, we would:
         // 1. Check if 'from' has enough 'source_asset'.
         // 2. Execute the swap/trade on the DEX.
         // 3. Ensure 'destination_amount' reaches the target.
-        // 4. Ensure cost didn't exceed 'send_max'.
 
-        // Check slippage (Mock logic)
-        // If current required source > send_max, fail
-        let current_required = send_max - 100; // Mock it's within limits
-        if current_required > send_max {
-             return false;
+        let quoted_source = item.source_amount;
+        let send_max = quoted_source
+            .checked_mul(BPS_DENOMINATOR + slippage_bps as i128)
+            .and_then(|v| v.checked_div(BPS_DENOMINATOR))
+            .ok_or(LancePayError::Overflow)?;
+
+        if required_source > send_max {
+            return Err(LancePayError::SlippageExceeded);
         }
 
         // Emit success event
         env.events().publish(
             (String::from_str(&env, "path_payment_success"), from),
-            current_required
+            required_source,
+        );
+
+        Ok(true)
+    }
+
+    fn convert(destination_amount: i128, rate: &ExchangeRate) -> Result<i128, LancePayError> {
+        destination_amount
+            .checked_mul(rate.numerator)
+            .and_then(|v| v.checked_div(rate.denominator))
+            .ok_or(LancePayError::Overflow)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+
+    fn setup() -> (Env, PathPaymentClient<'static>) {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, PathPayment);
+        let client = PathPaymentClient::new(&env, &contract_id);
+        (env, client)
+    }
+
+    #[test]
+    fn test_find_strict_receive_paths_quotes_both_routes() {
+        let (env, client) = setup();
+
+        let paths = client.find_strict_receive_paths(&Vec::new(&env), &String::from_str(&env, "USDC"), &100);
+
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths.get(0).unwrap().source_amount, 500);
+        assert_eq!(paths.get(1).unwrap().source_amount, 160_000);
+    }
+
+    #[test]
+    fn test_find_strict_receive_paths_rejects_overflowing_amount() {
+        let (env, client) = setup();
+
+        // destination_amount * 1600 overflows i128, so the FX conversion
+        // must fail closed instead of silently wrapping.
+        let result = client.try_find_strict_receive_paths(
+            &Vec::new(&env),
+            &String::from_str(&env, "USDC"),
+            &i128::MAX,
         );
+        assert_eq!(result, Err(Ok(LancePayError::Overflow)));
+    }
+
+    #[test]
+    fn test_execute_path_payment_succeeds_within_slippage_tolerance() {
+        let (env, client) = setup();
+        let from = Address::generate(&env);
+
+        let path = PaymentPath {
+            source_asset: String::from_str(&env, "XLM"),
+            source_amount: 500,
+            path: Vec::new(&env),
+            destination_amount: 100,
+        };
+
+        // 505 is within 5% (500 * 1.05 = 525) of the 500 quote.
+        let result = client.execute_path_payment(&from, &path, &505, &500);
+        assert!(result);
+    }
+
+    #[test]
+    fn test_execute_path_payment_rejects_when_required_source_exceeds_send_max() {
+        let (env, client) = setup();
+        let from = Address::generate(&env);
+
+        let path = PaymentPath {
+            source_asset: String::from_str(&env, "XLM"),
+            source_amount: 500,
+            path: Vec::new(&env),
+            destination_amount: 100,
+        };
 
-        true
+        // 600 exceeds 500 * 1.05 = 525, so the payment must be rejected.
+        let result = client.try_execute_path_payment(&from, &path, &600, &500);
+        assert_eq!(result, Err(Ok(LancePayError::SlippageExceeded)));
     }
 }
@@ -0,0 +1,192 @@
+use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, String, Vec};
+
+use crate::error::LancePayError;
+use crate::gasless::GaslessRelayer;
+use crate::path_payment::{PathPayment, PaymentPath};
+
+const BPS_DENOMINATOR: i128 = 10_000;
+
+enum PaymentStep {
+    FeeSponsorship,
+    PathResolution,
+    SlippageGuard,
+    Execution,
+}
+
+const PIPELINE: [PaymentStep; 4] = [
+    PaymentStep::FeeSponsorship,
+    PaymentStep::PathResolution,
+    PaymentStep::SlippageGuard,
+    PaymentStep::Execution,
+];
+
+#[contracttype]
+pub struct PaymentRequest {
+    pub source: Address,
+    pub destination_asset: String,
+    pub destination_amount: i128,
+    /// What the venue actually demands at execution time; compared against
+    /// the quote resolved by `PathResolution` under the slippage tolerance.
+    pub required_source: i128,
+    pub slippage_bps: u32,
+}
+
+#[derive(Debug, PartialEq)]
+#[contracttype]
+pub struct PaymentContext {
+    pub source: Address,
+    pub sponsor: Option<Address>,
+    /// Holds 0 or 1 entries; `soroban-sdk`'s `#[contracttype]` can't derive
+    /// XDR conversions for an `Option` of another `#[contracttype]` struct,
+    /// so a `Vec` stands in as the "maybe resolved" slot.
+    pub resolved_path: Vec<PaymentPath>,
+    pub send_max: i128,
+    pub executed: bool,
+}
+
+#[contract]
+pub struct PaymentPipeline;
+
+#[contractimpl]
+impl PaymentPipeline {
+    /// Runs a sponsored path payment through `FeeSponsorship` ->
+    /// `PathResolution` -> `SlippageGuard` -> `Execution`, composing
+    /// `gasless` sponsorship with `PathPayment` routing in one call.
+    pub fn submit_payment(
+        env: Env,
+        request: PaymentRequest,
+    ) -> Result<PaymentContext, LancePayError> {
+        let mut context = PaymentContext {
+            source: request.source.clone(),
+            sponsor: None,
+            resolved_path: Vec::new(&env),
+            send_max: 0,
+            executed: false,
+        };
+
+        for step in PIPELINE {
+            match step {
+                PaymentStep::FeeSponsorship => {
+                    context.sponsor =
+                        GaslessRelayer::resolve_sponsor(env.clone(), request.source.clone());
+                }
+                PaymentStep::PathResolution => {
+                    let paths = PathPayment::find_strict_receive_paths(
+                        env.clone(),
+                        Vec::new(&env),
+                        request.destination_asset.clone(),
+                        request.destination_amount,
+                    )?;
+                    let path = paths.get(0).ok_or(LancePayError::NoPathFound)?;
+                    context.resolved_path = Vec::from_array(&env, [path]);
+                }
+                PaymentStep::SlippageGuard => {
+                    let path = context
+                        .resolved_path
+                        .get(0)
+                        .ok_or(LancePayError::NoPathFound)?;
+                    context.send_max = path
+                        .source_amount
+                        .checked_mul(BPS_DENOMINATOR + request.slippage_bps as i128)
+                        .and_then(|v| v.checked_div(BPS_DENOMINATOR))
+                        .ok_or(LancePayError::Overflow)?;
+                    if request.required_source > context.send_max {
+                        return Err(LancePayError::SlippageExceeded);
+                    }
+                }
+                PaymentStep::Execution => {
+                    let path = context
+                        .resolved_path
+                        .get(0)
+                        .ok_or(LancePayError::NoPathFound)?;
+                    PathPayment::execute_path_payment(
+                        env.clone(),
+                        request.source.clone(),
+                        path,
+                        request.required_source,
+                        request.slippage_bps,
+                    )?;
+                    context.executed = true;
+                }
+            }
+        }
+
+        Ok(context)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+
+    fn setup() -> (Env, PaymentPipelineClient<'static>) {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, PaymentPipeline);
+        let client = PaymentPipelineClient::new(&env, &contract_id);
+        (env, client)
+    }
+
+    #[test]
+    fn test_submit_payment_round_trips_through_the_full_pipeline() {
+        let (env, client) = setup();
+        let source = Address::generate(&env);
+
+        let request = PaymentRequest {
+            source: source.clone(),
+            destination_asset: String::from_str(&env, "USDC"),
+            destination_amount: 100,
+            required_source: 505,
+            slippage_bps: 500,
+        };
+
+        let context = client.submit_payment(&request);
+
+        assert_eq!(context.source, source);
+        assert_eq!(context.sponsor, None);
+        assert_eq!(context.send_max, 525);
+        assert!(context.executed);
+    }
+
+    #[test]
+    fn test_submit_payment_rejects_when_required_source_exceeds_slippage_tolerance() {
+        let (env, client) = setup();
+        let source = Address::generate(&env);
+
+        let request = PaymentRequest {
+            source,
+            destination_asset: String::from_str(&env, "USDC"),
+            destination_amount: 100,
+            required_source: 600,
+            slippage_bps: 500,
+        };
+
+        let result = client.try_submit_payment(&request);
+        assert_eq!(result, Err(Ok(LancePayError::SlippageExceeded)));
+    }
+
+    #[test]
+    fn test_submit_payment_propagates_path_resolution_failure() {
+        let (env, client) = setup();
+        let source = Address::generate(&env);
+
+        // destination_amount large enough to overflow the mocked FX
+        // conversion, so PathResolution's `?` short-circuits the pipeline
+        // before SlippageGuard/Execution ever run. `find_strict_receive_paths`
+        // always returns at least one quote when it succeeds at all, so
+        // NoPathFound itself isn't reachable through this mocked path
+        // generator; this exercises the same short-circuit behavior.
+        let request = PaymentRequest {
+            source,
+            destination_asset: String::from_str(&env, "USDC"),
+            destination_amount: i128::MAX,
+            required_source: 0,
+            slippage_bps: 500,
+        };
+
+        let result = client.try_submit_payment(&request);
+        assert_eq!(result, Err(Ok(LancePayError::Overflow)));
+    }
+}